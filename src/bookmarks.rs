@@ -0,0 +1,74 @@
+//! Saved-connection bookmarks, persisted as TOML under
+//! `$HOME/.config/iscp/bookmarks.toml`. Only connection metadata is ever
+//! stored here — passwords and passphrases always come from the agent,
+//! key files, or an interactive prompt.
+
+use crate::transfer::Protocol;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub remote_path: String,
+    pub protocol: Protocol,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct BookmarkFile {
+    #[serde(default)]
+    bookmarks: Vec<Bookmark>,
+}
+
+fn bookmarks_path() -> Result<PathBuf, Box<dyn Error>> {
+    let home = std::env::var("HOME")?;
+    Ok(PathBuf::from(home)
+        .join(".config")
+        .join("iscp")
+        .join("bookmarks.toml"))
+}
+
+/// Loads saved bookmarks, returning an empty list if none are saved yet or
+/// the file can't be read — a missing bookmarks file isn't an error.
+pub fn load() -> Vec<Bookmark> {
+    let path = match bookmarks_path() {
+        Ok(path) => path,
+        Err(_) => return Vec::new(),
+    };
+
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(_) => return Vec::new(),
+    };
+
+    toml::from_str::<BookmarkFile>(&contents)
+        .map(|file| file.bookmarks)
+        .unwrap_or_default()
+}
+
+fn save(bookmarks: &[Bookmark]) -> Result<(), Box<dyn Error>> {
+    let path = bookmarks_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = BookmarkFile {
+        bookmarks: bookmarks.to_vec(),
+    };
+    fs::write(&path, toml::to_string_pretty(&file)?)?;
+
+    Ok(())
+}
+
+/// Adds a bookmark, replacing any existing one with the same name.
+pub fn add(bookmark: Bookmark) -> Result<(), Box<dyn Error>> {
+    let mut bookmarks = load();
+    bookmarks.retain(|existing| existing.name != bookmark.name);
+    bookmarks.push(bookmark);
+    save(&bookmarks)
+}
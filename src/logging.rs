@@ -0,0 +1,88 @@
+//! Opt-in structured logging for diagnosing failed transfers. Enabled by
+//! `--log` on the command line or the `ISCP_LOG` env var, this writes a
+//! timestamped, machine-parseable log to `$HOME/.cache/iscp/iscp.log` so a
+//! user can attach it to a bug report instead of transcribing the emoji
+//! console output by hand.
+
+use chrono::Utc;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::error::Error;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+struct FileLogger {
+    file: Mutex<File>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Debug
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let Ok(mut file) = self.file.lock() else {
+            return;
+        };
+
+        let _ = writeln!(
+            file,
+            "{} {:<5} {}",
+            Utc::now().to_rfc3339(),
+            record.level(),
+            record.args()
+        );
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            let _ = file.flush();
+        }
+    }
+}
+
+fn log_path() -> Result<PathBuf, Box<dyn Error>> {
+    let home = std::env::var("HOME")?;
+    Ok(PathBuf::from(home)
+        .join(".cache")
+        .join("iscp")
+        .join("iscp.log"))
+}
+
+/// Sets up the file-backed logger. Safe to call at most once per process
+/// (as with any `log` backend).
+pub fn init() -> Result<(), Box<dyn Error>> {
+    let path = log_path()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let logger = Box::new(FileLogger {
+        file: Mutex::new(file),
+    });
+
+    log::set_boxed_logger(logger)?;
+    log::set_max_level(LevelFilter::Debug);
+
+    log::info!("iscp starting, logging to {}", path.display());
+
+    Ok(())
+}
+
+/// Logs an error together with its full `source()` chain, so a failure
+/// deep in ssh2/sftp isn't flattened to its outermost message.
+pub fn log_error_chain(context: &str, err: &(dyn Error + 'static)) {
+    log::error!("{}: {}", context, err);
+
+    let mut source = err.source();
+    while let Some(cause) = source {
+        log::error!("caused by: {}", cause);
+        source = cause.source();
+    }
+}
@@ -1,23 +1,100 @@
+mod bookmarks;
+mod logging;
+mod transfer;
+
+use bookmarks::Bookmark;
+use clap::Parser;
 use console::style;
-use dialoguer::{Input, Password};
+use dialoguer::{Confirm, Input, Select};
 use indicatif::{ProgressBar, ProgressStyle};
-use ssh2::Session;
-use std::fs::File;
-use std::io::{Read, Write};
-use std::net::TcpStream;
 use std::path::Path;
 use std::process;
+use std::time::Duration;
+use transfer::ssh_common::AuthMethod;
+use transfer::{FileTransfer, Protocol};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TransferDirection {
+    Upload,
+    Download,
+}
 
 #[derive(Debug)]
 struct TransferConfig {
+    protocol: Protocol,
+    direction: TransferDirection,
     local_file: String,
     remote_host: String,
     port: u16,
     remote_path: String,
     username: String,
+    auth_method: AuthMethod,
+    identity_file: Option<String>,
+}
+
+/// Command-line arguments. Anything left unset here falls back to an
+/// interactive prompt, unless `--batch` is given, in which case a missing
+/// required value is a hard error so the binary can run unattended.
+#[derive(Parser, Debug)]
+#[command(name = "iscp", version, about = "Interactive/scriptable SCP, SFTP & FTP file transfer")]
+struct Cli {
+    /// Local file or directory (upload source, or download destination)
+    #[arg(short = 'l', long = "local")]
+    local_file: Option<String>,
+
+    /// Remote host
+    #[arg(long)]
+    host: Option<String>,
+
+    /// Remote port (defaults to the protocol's standard port)
+    #[arg(short, long)]
+    port: Option<u16>,
+
+    /// Username
+    #[arg(short, long)]
+    username: Option<String>,
+
+    /// Remote path (upload destination, or download source)
+    #[arg(short, long = "remote")]
+    remote_path: Option<String>,
+
+    /// Transfer protocol
+    #[arg(long, value_enum)]
+    protocol: Option<Protocol>,
+
+    /// Transfer direction
+    #[arg(long, value_enum)]
+    direction: Option<TransferDirection>,
+
+    /// Authentication method (defaults to trying agent, then keys, then password)
+    #[arg(long, value_enum)]
+    auth: Option<AuthMethod>,
+
+    /// Specific private key file to use
+    #[arg(long)]
+    identity_file: Option<String>,
+
+    /// Write a diagnostic log to $HOME/.cache/iscp/iscp.log (same as ISCP_LOG)
+    #[arg(long)]
+    log: bool,
+
+    /// Fail instead of prompting when a required value is missing, for unattended/CI use
+    #[arg(long)]
+    batch: bool,
 }
 
 fn main() {
+    let cli = Cli::parse();
+
+    if cli.log || std::env::var("ISCP_LOG").is_ok() {
+        if let Err(e) = logging::init() {
+            eprintln!(
+                "{}",
+                style(format!("⚠️  Failed to start logging: {}", e)).yellow()
+            );
+        }
+    }
+
     println!("{}", style("=====================================").cyan());
     println!(
         "{}",
@@ -25,20 +102,25 @@ fn main() {
     );
     println!("{}", style("=====================================").cyan());
 
-    // Get transfer configuration from user
-    let config = get_transfer_config();
+    // Get transfer configuration from CLI args and/or interactive prompts
+    let config = get_transfer_config(&cli);
 
     // Perform the file transfer
     match transfer_file(&config) {
         Ok(_) => {
+            log::info!("transfer completed successfully");
             println!(
                 "\n{}",
                 style("✅ File transfer completed successfully!")
                     .green()
                     .bold()
             );
+            if !cli.batch {
+                maybe_save_bookmark(&config);
+            }
         }
         Err(e) => {
+            logging::log_error_chain("transfer failed", e.as_ref());
             eprintln!("\n{}", style("❌ Transfer failed:").red().bold());
             eprintln!("{}", style(e).red());
             process::exit(1);
@@ -46,216 +128,380 @@ fn main() {
     }
 }
 
-fn get_transfer_config() -> TransferConfig {
+/// Prints an error and exits if `--batch` was given and a required value
+/// is missing; otherwise a no-op, letting the caller fall through to an
+/// interactive prompt.
+fn require_in_batch_mode(cli: &Cli, flag: &str) {
+    if cli.batch {
+        eprintln!(
+            "{}",
+            style(format!("❌ --batch mode requires --{} to be set", flag)).red().bold()
+        );
+        process::exit(1);
+    }
+}
+
+fn get_transfer_config(cli: &Cli) -> TransferConfig {
+    // Offer saved bookmarks instead of re-typing host/port/username/remote
+    // path, but only when we're not scripted and the host wasn't already
+    // given on the command line.
+    let saved = bookmarks::load();
+    let bookmark = if cli.batch || cli.host.is_some() || saved.is_empty() {
+        None
+    } else {
+        let mut items: Vec<String> = saved
+            .iter()
+            .map(|b| format!("{} ({}@{}:{}, {})", b.name, b.username, b.host, b.port, b.protocol.label()))
+            .collect();
+        items.push("+ New connection".to_string());
+
+        let idx = Select::new()
+            .with_prompt("🔖 Saved connections")
+            .items(&items)
+            .default(0)
+            .interact()
+            .expect("Failed to read bookmark selection");
+
+        if idx < saved.len() {
+            Some(saved[idx].clone())
+        } else {
+            None
+        }
+    };
+
+    // Get protocol
+    let protocol = match (&cli.protocol, &bookmark) {
+        (Some(protocol), _) => *protocol,
+        (None, Some(bookmark)) => bookmark.protocol,
+        (None, None) => {
+            require_in_batch_mode(cli, "protocol");
+            let protocol_labels: Vec<&str> = Protocol::ALL.iter().map(Protocol::label).collect();
+            let protocol_idx = Select::new()
+                .with_prompt("🧭 Protocol")
+                .items(&protocol_labels)
+                .default(0)
+                .interact()
+                .expect("Failed to read protocol");
+            Protocol::ALL[protocol_idx]
+        }
+    };
+
+    if protocol == Protocol::Ftp && matches!(cli.auth, Some(AuthMethod::Agent) | Some(AuthMethod::Key)) {
+        eprintln!(
+            "{}",
+            style("❌ --auth agent/key only applies to SCP/SFTP; FTP always authenticates with a password")
+                .red()
+                .bold()
+        );
+        process::exit(1);
+    }
+
+    // Get transfer direction (defaults to Upload, matching the tool's
+    // original upload-only behavior, if unset even in batch mode)
+    let direction = match cli.direction {
+        Some(direction) => direction,
+        None if cli.batch => TransferDirection::Upload,
+        None => {
+            let directions = ["⬆️  Upload (local → remote)", "⬇️  Download (remote → local)"];
+            let direction_idx = Select::new()
+                .with_prompt("📡 Transfer direction")
+                .items(&directions)
+                .default(0)
+                .interact()
+                .expect("Failed to read transfer direction");
+
+            if direction_idx == 0 {
+                TransferDirection::Upload
+            } else {
+                TransferDirection::Download
+            }
+        }
+    };
+
     // Get local file path
-    let local_file: String = Input::new()
-        .with_prompt("📁 Local file path")
-        .interact()
-        .expect("Failed to read local file path");
+    let local_file = match &cli.local_file {
+        Some(local_file) => local_file.clone(),
+        None => {
+            require_in_batch_mode(cli, "local");
+            let local_prompt = match direction {
+                TransferDirection::Upload => "📁 Local file path",
+                TransferDirection::Download => "📁 Local destination path",
+            };
+            Input::new()
+                .with_prompt(local_prompt)
+                .interact()
+                .expect("Failed to read local file path")
+        }
+    };
 
-    if !Path::new(&local_file).exists() {
+    if direction == TransferDirection::Upload && !Path::new(&local_file).exists() {
         eprintln!("{}", style("❌ Local file does not exist!").red().bold());
         process::exit(1);
     }
 
-    // Get remote host
-    let remote_host: String = Input::new()
-        .with_prompt("🌐 Remote host (e.g., example.com or 192.168.1.100)")
-        .interact()
-        .expect("Failed to read remote host");
+    if direction == TransferDirection::Upload && Path::new(&local_file).is_dir() && protocol != Protocol::Sftp {
+        eprintln!(
+            "{}",
+            style(format!(
+                "❌ Directory uploads are only supported over SFTP (got {})",
+                protocol.label()
+            ))
+            .red()
+            .bold()
+        );
+        process::exit(1);
+    }
 
-    // Get port (optional)
-    let port_input: String = Input::new()
-        .with_prompt("🔌 Port (optional, press Enter for default 22)")
-        .allow_empty(true)
-        .interact()
-        .expect("Failed to read port");
+    // Get remote host
+    let remote_host = match (&cli.host, &bookmark) {
+        (Some(host), _) => host.clone(),
+        (None, Some(bookmark)) => bookmark.host.clone(),
+        (None, None) => {
+            require_in_batch_mode(cli, "host");
+            Input::new()
+                .with_prompt("🌐 Remote host (e.g., example.com or 192.168.1.100)")
+                .interact()
+                .expect("Failed to read remote host")
+        }
+    };
 
-    let port = if port_input.is_empty() {
-        22
-    } else {
-        port_input.parse::<u16>().unwrap_or_else(|_| {
-            eprintln!(
-                "{}",
-                style("❌ Invalid port number, using default 22").yellow()
-            );
-            22
-        })
+    // Get port (optional, falls back to the protocol's standard port)
+    let default_port = match protocol {
+        Protocol::Ftp => 21,
+        Protocol::Scp | Protocol::Sftp => 22,
+    };
+    let port = match (cli.port, &bookmark) {
+        (Some(port), _) => port,
+        (None, Some(bookmark)) => bookmark.port,
+        (None, None) if cli.batch => default_port,
+        (None, None) => {
+            let port_input: String = Input::new()
+                .with_prompt(&format!(
+                    "🔌 Port (optional, press Enter for default {})",
+                    default_port
+                ))
+                .allow_empty(true)
+                .interact()
+                .expect("Failed to read port");
+
+            if port_input.is_empty() {
+                default_port
+            } else {
+                port_input.parse::<u16>().unwrap_or_else(|_| {
+                    eprintln!(
+                        "{}",
+                        style(format!("❌ Invalid port number, using default {}", default_port)).yellow()
+                    );
+                    default_port
+                })
+            }
+        }
     };
 
     // Get username
-    let username: String = Input::new()
-        .with_prompt("👤 Username")
-        .interact()
-        .expect("Failed to read username");
-
-    // Get remote path (optional)
-    let local_filename = Path::new(&local_file)
-        .file_name()
-        .unwrap()
-        .to_string_lossy()
-        .to_string();
-
-    let default_remote_path = format!("/home/{}/{}", username, local_filename);
-
-    let remote_path: String = Input::new()
-        .with_prompt(&format!(
-            "📂 Remote path (optional, press Enter for default: {})",
-            default_remote_path
-        ))
-        .allow_empty(true)
-        .interact()
-        .expect("Failed to read remote path");
+    let username = match (&cli.username, &bookmark) {
+        (Some(username), _) => username.clone(),
+        (None, Some(bookmark)) => bookmark.username.clone(),
+        (None, None) => {
+            require_in_batch_mode(cli, "username");
+            Input::new()
+                .with_prompt("👤 Username")
+                .interact()
+                .expect("Failed to read username")
+        }
+    };
 
-    let final_remote_path = if remote_path.is_empty() {
-        default_remote_path
-    } else {
-        remote_path
+    // Get remote path
+    let remote_path = match &cli.remote_path {
+        Some(remote_path) => remote_path.clone(),
+        None => match direction {
+            TransferDirection::Upload => {
+                // Optional, defaults to the bookmark's path or the local
+                // file's name in the user's home dir.
+                let default_remote_path = bookmark.as_ref().map(|b| b.remote_path.clone()).unwrap_or_else(|| {
+                    // `.`, `..`, and `/` have no file-name component; fall
+                    // back to the canonicalized path's name, and if even
+                    // that fails, just drop the file into the home dir.
+                    let local_filename = Path::new(&local_file)
+                        .file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .or_else(|| {
+                            Path::new(&local_file)
+                                .canonicalize()
+                                .ok()?
+                                .file_name()
+                                .map(|name| name.to_string_lossy().to_string())
+                        })
+                        .unwrap_or_else(|| "upload".to_string());
+                    format!("/home/{}/{}", username, local_filename)
+                });
+
+                if cli.batch {
+                    default_remote_path
+                } else {
+                    let remote_path: String = Input::new()
+                        .with_prompt(&format!(
+                            "📂 Remote path (optional, press Enter for default: {})",
+                            default_remote_path
+                        ))
+                        .allow_empty(true)
+                        .interact()
+                        .expect("Failed to read remote path");
+
+                    if remote_path.is_empty() {
+                        default_remote_path
+                    } else {
+                        remote_path
+                    }
+                }
+            }
+            TransferDirection::Download => {
+                // Required: this is the file being pulled down. A bookmark's
+                // path is only a default here too, same as for Upload — the
+                // user can still pull a different remote file.
+                if let Some(bookmark) = &bookmark {
+                    if cli.batch {
+                        bookmark.remote_path.clone()
+                    } else {
+                        let remote_path: String = Input::new()
+                            .with_prompt(&format!(
+                                "📂 Remote file path (optional, press Enter for default: {})",
+                                bookmark.remote_path
+                            ))
+                            .allow_empty(true)
+                            .interact()
+                            .expect("Failed to read remote path");
+
+                        if remote_path.is_empty() {
+                            bookmark.remote_path.clone()
+                        } else {
+                            remote_path
+                        }
+                    }
+                } else {
+                    require_in_batch_mode(cli, "remote");
+                    Input::new()
+                        .with_prompt("📂 Remote file path")
+                        .interact()
+                        .expect("Failed to read remote path")
+                }
+            }
+        },
     };
 
     TransferConfig {
+        protocol,
+        direction,
         local_file,
         remote_host,
         port,
-        remote_path: final_remote_path,
+        remote_path,
         username,
+        auth_method: cli.auth.unwrap_or_default(),
+        identity_file: cli.identity_file.clone(),
+    }
+}
+
+/// After a successful transfer, offers to save the connection parameters
+/// (never a password) as a named bookmark for next time.
+fn maybe_save_bookmark(config: &TransferConfig) {
+    let should_save = Confirm::new()
+        .with_prompt("💾 Save this connection as a bookmark?")
+        .default(false)
+        .interact()
+        .unwrap_or(false);
+
+    if !should_save {
+        return;
+    }
+
+    let name: String = match Input::new().with_prompt("🏷️  Bookmark name").interact() {
+        Ok(name) => name,
+        Err(_) => return,
+    };
+
+    let bookmark = Bookmark {
+        name,
+        host: config.remote_host.clone(),
+        port: config.port,
+        username: config.username.clone(),
+        remote_path: config.remote_path.clone(),
+        protocol: config.protocol,
+    };
+
+    match bookmarks::add(bookmark) {
+        Ok(_) => println!("{}", style("✅ Bookmark saved").green()),
+        Err(e) => eprintln!("{}", style(format!("⚠️  Failed to save bookmark: {}", e)).yellow()),
     }
 }
 
 fn transfer_file(config: &TransferConfig) -> Result<(), Box<dyn std::error::Error>> {
     println!("\n{}", style("🔗 Connecting to remote host...").blue());
+    log::info!(
+        "connecting to {}:{} via {} ({:?})",
+        config.remote_host,
+        config.port,
+        config.protocol.label(),
+        config.direction
+    );
 
-    // Establish TCP connection
-    let tcp = TcpStream::connect(format!("{}:{}", config.remote_host, config.port))?;
-    let mut sess = Session::new()?;
-    sess.set_tcp_stream(tcp);
-    sess.handshake()?;
+    let mut backend = transfer::backend_for(config.protocol);
+    backend.connect(config)?;
 
-    // Try to authenticate
-    if !authenticate(&mut sess, config)? {
+    if !backend.authenticate(config)? {
+        log::warn!("authentication failed for {}@{}", config.username, config.remote_host);
         return Err("Authentication failed".into());
     }
 
+    log::info!("authenticated as {}", config.username);
     println!(
         "{}",
         style("✅ Connected and authenticated successfully!").green()
     );
-    println!("{}", style("📤 Starting file transfer...").blue());
-
-    // Read local file
-    let mut local_file = File::open(&config.local_file)?;
-    let file_size = local_file.metadata()?.len();
-
-    // Create progress bar
-    let progress_bar = ProgressBar::new(file_size);
-    progress_bar.set_style(
-            ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
-                .unwrap()
-                .progress_chars("#>-"),
-        );
-
-    // Create SCP channel for file transfer
-    let mut channel = sess.scp_send(Path::new(&config.remote_path), 0o644, file_size, None)?;
 
-    // Transfer file with progress tracking
-    let mut buffer = [0; 8192];
-    let mut transferred = 0;
-
-    loop {
-        let bytes_read = local_file.read(&mut buffer)?;
-        if bytes_read == 0 {
-            break;
+    let size_hint = backend.size_hint(config)?;
+    let progress_bar = match size_hint {
+        Some(total) => {
+            let bar = ProgressBar::new(total);
+            bar.set_style(
+                ProgressStyle::default_bar()
+                    .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+                    .unwrap(),
+            );
+            bar
+        }
+        None => {
+            let bar = ProgressBar::new_spinner();
+            bar.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{spinner:.green} [{elapsed_precise}] {bytes} transferred ({binary_bytes_per_sec})")
+                    .unwrap(),
+            );
+            bar
         }
+    };
+    progress_bar.enable_steady_tick(Duration::from_millis(100));
 
-        channel.write_all(&buffer[..bytes_read])?;
-        transferred += bytes_read as u64;
+    let mut transferred = 0u64;
+    let mut on_chunk = |delta: u64| {
+        transferred += delta;
         progress_bar.set_position(transferred);
-    }
-
-    // Close the channel
-    channel.send_eof()?;
-    channel.wait_eof()?;
-    channel.close()?;
-    channel.wait_close()?;
-
-    progress_bar.finish_with_message("Transfer completed!");
-
-    Ok(())
-}
-
-fn authenticate(
-    sess: &mut Session,
-    config: &TransferConfig,
-) -> Result<bool, Box<dyn std::error::Error>> {
-    // First, try to authenticate with SSH keys
-    if let Ok(home) = std::env::var("HOME") {
-        let key_paths = [
-            format!("{}/.ssh/id_rsa", home),
-            format!("{}/.ssh/id_ed25519", home),
-            format!("{}/.ssh/id_ecdsa", home),
-        ];
-
-        for key_path in &key_paths {
-            if Path::new(key_path).exists() {
-                println!(
-                    "{}",
-                    style(format!("🔑 Trying SSH key: {}", key_path)).blue()
-                );
-
-                let key_path = Path::new(key_path);
-
-                // Try without passphrase first
-                if let Ok(_) = sess.userauth_pubkey_file(&config.username, None, &key_path, None) {
-                    println!(
-                        "{}",
-                        style("✅ Authenticated with SSH key (no passphrase)").green()
-                    );
-                    return Ok(true);
-                }
+    };
 
-                // Key requires passphrase
-                println!("{}", style("🔐 SSH key requires passphrase").yellow());
-                let passphrase: String = Password::new()
-                    .with_prompt("🔑 SSH key passphrase")
-                    .interact()
-                    .expect("Failed to read passphrase");
-
-                if let Ok(_) = sess.userauth_pubkey_file(
-                    &config.username,
-                    None,
-                    &key_path,
-                    Some(passphrase.as_str()),
-                ) {
-                    println!(
-                        "{}",
-                        style("✅ Authenticated with SSH key (with passphrase)").green()
-                    );
-                    return Ok(true);
-                }
-            }
+    match config.direction {
+        TransferDirection::Upload => {
+            println!("{}", style("📤 Starting file transfer...").blue());
+            backend.send(config, &mut on_chunk)?;
+        }
+        TransferDirection::Download => {
+            println!("{}", style("📥 Starting file transfer...").blue());
+            backend.recv(config, &mut on_chunk)?;
         }
     }
 
-    // Fallback to password authentication
-    println!(
-        "{}",
-        style("🔐 SSH key authentication failed, trying password authentication").yellow()
-    );
-
-    let password: String = Input::new()
-        .with_prompt("🔑 Password")
-        .interact()
-        .expect("Failed to read password");
+    progress_bar.finish_with_message("Transfer completed!");
+    log::info!("transferred {} bytes", transferred);
+    backend.disconnect()?;
 
-    match sess.userauth_password(&config.username, &password) {
-        Ok(_) => {
-            println!("{}", style("✅ Authenticated with password").green());
-            Ok(true)
-        }
-        _ => {
-            println!("{}", style("❌ Password authentication failed").red());
-            Ok(false)
-        }
-    }
+    Ok(())
 }
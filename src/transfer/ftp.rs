@@ -0,0 +1,119 @@
+use super::FileTransfer;
+use crate::{TransferConfig, TransferDirection};
+use dialoguer::Password;
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use suppaftp::FtpStream;
+
+/// Plain FTP backend for servers that don't expose SSH at all, using a
+/// pure-Rust FTP client so we don't need a system libftp.
+#[derive(Default)]
+pub struct FtpTransfer {
+    stream: Option<FtpStream>,
+}
+
+impl FtpTransfer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn stream(&mut self) -> Result<&mut FtpStream, Box<dyn Error>> {
+        self.stream.as_mut().ok_or_else(|| "Not connected".into())
+    }
+}
+
+impl FileTransfer for FtpTransfer {
+    fn connect(&mut self, config: &TransferConfig) -> Result<(), Box<dyn Error>> {
+        let stream = FtpStream::connect(format!("{}:{}", config.remote_host, config.port))?;
+        log::debug!("FTP connected to {}:{}", config.remote_host, config.port);
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn authenticate(&mut self, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+        let password: String = Password::new()
+            .with_prompt("🔑 FTP password")
+            .interact()?;
+
+        let stream = self.stream()?;
+        let authenticated = stream.login(&config.username, &password).is_ok();
+        if authenticated {
+            log::info!("authenticated with FTP password as {}", config.username);
+        } else {
+            log::warn!("FTP password auth rejected for {}", config.username);
+        }
+        Ok(authenticated)
+    }
+
+    fn size_hint(&mut self, config: &TransferConfig) -> Result<Option<u64>, Box<dyn Error>> {
+        match config.direction {
+            TransferDirection::Upload => Ok(Some(std::fs::metadata(&config.local_file)?.len())),
+            TransferDirection::Download => {
+                let stream = self.stream()?;
+                Ok(stream.size(&config.remote_path).ok().map(|size| size as u64))
+            }
+        }
+    }
+
+    fn send(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        log::debug!("FTP upload starting: {} -> {}", config.local_file, config.remote_path);
+        let mut local_file = File::open(&config.local_file)?;
+        let stream = self.stream()?;
+        let mut counted = ChunkCounter {
+            inner: &mut local_file,
+            on_chunk,
+        };
+        stream.put_file(&config.remote_path, &mut counted)?;
+        log::info!("FTP upload complete: {}", config.remote_path);
+        Ok(())
+    }
+
+    fn recv(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        log::debug!("FTP download starting: {} -> {}", config.remote_path, config.local_file);
+        let stream = self.stream()?;
+        let mut remote_reader = stream.retr_as_stream(&config.remote_path)?;
+        let mut local_file = File::create(&config.local_file)?;
+
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = remote_reader.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            local_file.write_all(&buffer[..bytes_read])?;
+            on_chunk(bytes_read as u64);
+        }
+
+        stream.finalize_retr_stream(remote_reader)?;
+        log::info!("FTP download complete: {}", config.remote_path);
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), Box<dyn Error>> {
+        if let Some(mut stream) = self.stream.take() {
+            let _ = stream.quit();
+            log::debug!("FTP connection closed");
+        }
+        Ok(())
+    }
+}
+
+/// Wraps a `Read` to report every chunk pulled through it, so uploads can
+/// feed the same progress callback as downloads without buffering the
+/// whole file first.
+struct ChunkCounter<'a, R> {
+    inner: &'a mut R,
+    on_chunk: &'a mut dyn FnMut(u64),
+}
+
+impl<'a, R: Read> Read for ChunkCounter<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            (self.on_chunk)(n as u64);
+        }
+        Ok(n)
+    }
+}
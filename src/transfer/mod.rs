@@ -0,0 +1,61 @@
+mod ftp;
+mod scp;
+mod sftp;
+pub mod ssh_common;
+
+pub use ftp::FtpTransfer;
+pub use scp::ScpTransfer;
+pub use sftp::SftpTransfer;
+
+use crate::TransferConfig;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// Protocol a `FileTransfer` backend speaks. Chosen in `get_transfer_config`
+/// (from `--protocol`, a saved bookmark, or an interactive prompt) and
+/// stored on `TransferConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Protocol {
+    Scp,
+    Sftp,
+    Ftp,
+}
+
+impl Protocol {
+    pub const ALL: [Protocol; 3] = [Protocol::Scp, Protocol::Sftp, Protocol::Ftp];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Protocol::Scp => "SCP",
+            Protocol::Sftp => "SFTP",
+            Protocol::Ftp => "FTP",
+        }
+    }
+}
+
+/// A transfer backend capable of moving a file (and, where supported, a
+/// directory tree) to or from a remote host. `send`/`recv` report progress
+/// through `on_chunk`, invoked with the byte delta of each chunk moved, so
+/// the caller can drive a single progress bar regardless of which protocol
+/// is in play.
+pub trait FileTransfer {
+    fn connect(&mut self, config: &TransferConfig) -> Result<(), Box<dyn Error>>;
+    fn authenticate(&mut self, config: &TransferConfig) -> Result<bool, Box<dyn Error>>;
+    /// Total bytes the upcoming `send`/`recv` will move, when it can be
+    /// known up front without fully walking the transfer (e.g. a single
+    /// file's size). `None` means the caller should show an indeterminate
+    /// progress indicator instead of a percentage/ETA.
+    fn size_hint(&mut self, config: &TransferConfig) -> Result<Option<u64>, Box<dyn Error>>;
+    fn send(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>>;
+    fn recv(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>>;
+    fn disconnect(&mut self) -> Result<(), Box<dyn Error>>;
+}
+
+/// Builds the backend for the protocol the user picked.
+pub fn backend_for(protocol: Protocol) -> Box<dyn FileTransfer> {
+    match protocol {
+        Protocol::Scp => Box::new(ScpTransfer::new()),
+        Protocol::Sftp => Box::new(SftpTransfer::new()),
+        Protocol::Ftp => Box::new(FtpTransfer::new()),
+    }
+}
@@ -0,0 +1,114 @@
+use super::ssh_common::SshConnection;
+use super::FileTransfer;
+use crate::{TransferConfig, TransferDirection};
+use ssh2::Channel;
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// The original SCP backend: a single file over an `ssh2` channel, no
+/// directory support.
+#[derive(Default)]
+pub struct ScpTransfer {
+    conn: SshConnection,
+    /// SCP has no separate stat primitive, so a Download's `size_hint` has
+    /// to open the `scp_recv` channel to learn the size. Cached here so
+    /// `recv` reuses it instead of opening a second one.
+    pending_recv: Option<(Channel, u64)>,
+}
+
+impl ScpTransfer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FileTransfer for ScpTransfer {
+    fn connect(&mut self, config: &TransferConfig) -> Result<(), Box<dyn Error>> {
+        self.conn.connect(config)
+    }
+
+    fn authenticate(&mut self, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+        self.conn.authenticate(config)
+    }
+
+    fn size_hint(&mut self, config: &TransferConfig) -> Result<Option<u64>, Box<dyn Error>> {
+        match config.direction {
+            TransferDirection::Upload => Ok(Some(std::fs::metadata(&config.local_file)?.len())),
+            TransferDirection::Download => {
+                let sess = self.conn.session()?;
+                let (channel, stat) = sess.scp_recv(Path::new(&config.remote_path))?;
+                let file_size = stat.size();
+                self.pending_recv = Some((channel, file_size));
+                Ok(Some(file_size))
+            }
+        }
+    }
+
+    fn send(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        let sess = self.conn.session()?;
+        let mut local_file = File::open(&config.local_file)?;
+        let file_size = local_file.metadata()?.len();
+
+        let mut channel = sess.scp_send(Path::new(&config.remote_path), 0o644, file_size, None)?;
+
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = local_file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            channel.write_all(&buffer[..bytes_read])?;
+            on_chunk(bytes_read as u64);
+        }
+
+        channel.send_eof()?;
+        channel.wait_eof()?;
+        channel.close()?;
+        channel.wait_close()?;
+
+        Ok(())
+    }
+
+    fn recv(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        let (mut channel, file_size) = if let Some(pending) = self.pending_recv.take() {
+            pending
+        } else {
+            let sess = self.conn.session()?;
+            let (channel, stat) = sess.scp_recv(Path::new(&config.remote_path))?;
+            (channel, stat.size())
+        };
+
+        let mut local_file = File::create(&config.local_file)?;
+
+        let mut buffer = [0; 8192];
+        let mut transferred = 0u64;
+
+        while transferred < file_size {
+            let to_read = std::cmp::min(buffer.len() as u64, file_size - transferred) as usize;
+            let bytes_read = channel.read(&mut buffer[..to_read])?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            local_file.write_all(&buffer[..bytes_read])?;
+            transferred += bytes_read as u64;
+            on_chunk(bytes_read as u64);
+        }
+
+        channel.send_eof()?;
+        channel.wait_eof()?;
+        channel.close()?;
+        channel.wait_close()?;
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), Box<dyn Error>> {
+        self.pending_recv = None;
+        self.conn.disconnect();
+        Ok(())
+    }
+}
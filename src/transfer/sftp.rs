@@ -0,0 +1,190 @@
+use super::ssh_common::SshConnection;
+use super::FileTransfer;
+use crate::{TransferConfig, TransferDirection};
+use ssh2::{FileStat, Sftp};
+use std::error::Error;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+
+/// SFTP backend. Unlike `ScpTransfer`, this one can also push a whole
+/// local directory tree.
+#[derive(Default)]
+pub struct SftpTransfer {
+    conn: SshConnection,
+}
+
+impl SftpTransfer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl FileTransfer for SftpTransfer {
+    fn connect(&mut self, config: &TransferConfig) -> Result<(), Box<dyn Error>> {
+        self.conn.connect(config)
+    }
+
+    fn authenticate(&mut self, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+        self.conn.authenticate(config)
+    }
+
+    fn size_hint(&mut self, config: &TransferConfig) -> Result<Option<u64>, Box<dyn Error>> {
+        match config.direction {
+            TransferDirection::Upload => {
+                let local_path = Path::new(&config.local_file);
+                if local_path.is_dir() {
+                    let total = collect_files(local_path)?
+                        .iter()
+                        .map(|path| path.metadata().map(|meta| meta.len()).unwrap_or(0))
+                        .sum();
+                    Ok(Some(total))
+                } else {
+                    Ok(Some(local_path.metadata()?.len()))
+                }
+            }
+            TransferDirection::Download => {
+                let sess = self.conn.session()?;
+                let sftp = sess.sftp()?;
+                let stat = sftp.stat(Path::new(&config.remote_path))?;
+                Ok(stat.size)
+            }
+        }
+    }
+
+    fn send(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        let sess = self.conn.session()?;
+        let sftp = sess.sftp()?;
+        let local_path = Path::new(&config.local_file);
+        let remote_path = Path::new(&config.remote_path);
+
+        if local_path.is_dir() {
+            upload_directory(&sftp, local_path, remote_path, on_chunk)
+        } else {
+            upload_one(&sftp, local_path, remote_path, on_chunk)
+        }
+    }
+
+    fn recv(&mut self, config: &TransferConfig, on_chunk: &mut dyn FnMut(u64)) -> Result<(), Box<dyn Error>> {
+        let sess = self.conn.session()?;
+        let sftp = sess.sftp()?;
+        let mut remote_file = sftp.open(Path::new(&config.remote_path))?;
+        let mut local_file = File::create(&config.local_file)?;
+
+        let mut buffer = [0; 8192];
+        loop {
+            let bytes_read = remote_file.read(&mut buffer)?;
+            if bytes_read == 0 {
+                break;
+            }
+
+            local_file.write_all(&buffer[..bytes_read])?;
+            on_chunk(bytes_read as u64);
+        }
+
+        Ok(())
+    }
+
+    fn disconnect(&mut self) -> Result<(), Box<dyn Error>> {
+        self.conn.disconnect();
+        Ok(())
+    }
+}
+
+fn upload_one(
+    sftp: &Sftp,
+    local_path: &Path,
+    remote_path: &Path,
+    on_chunk: &mut dyn FnMut(u64),
+) -> Result<(), Box<dyn Error>> {
+    let mode = local_path.metadata()?.permissions().mode() & 0o777;
+
+    let mut local_file = File::open(local_path)?;
+    let mut remote_file = sftp.create(remote_path)?;
+    sftp.setstat(
+        remote_path,
+        FileStat {
+            size: None,
+            uid: None,
+            gid: None,
+            perm: Some(mode),
+            atime: None,
+            mtime: None,
+        },
+    )?;
+
+    let mut buffer = [0; 8192];
+    loop {
+        let bytes_read = local_file.read(&mut buffer)?;
+        if bytes_read == 0 {
+            break;
+        }
+
+        remote_file.write_all(&buffer[..bytes_read])?;
+        on_chunk(bytes_read as u64);
+    }
+
+    Ok(())
+}
+
+/// Uploads a whole local directory tree, preserving Unix permission bits.
+fn upload_directory(
+    sftp: &Sftp,
+    local_root: &Path,
+    remote_root: &Path,
+    on_chunk: &mut dyn FnMut(u64),
+) -> Result<(), Box<dyn Error>> {
+    for local_path in collect_files(local_root)? {
+        let relative = local_path.strip_prefix(local_root)?;
+        let remote_path = remote_root.join(relative);
+
+        if let Some(remote_parent) = remote_path.parent() {
+            ensure_remote_dir(sftp, remote_parent)?;
+        }
+
+        upload_one(sftp, &local_path, &remote_path, on_chunk)?;
+    }
+
+    Ok(())
+}
+
+/// Walks a local directory depth-first, returning every regular file
+/// found anywhere in the tree.
+fn collect_files(root: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+
+    while let Some(dir) = stack.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path.is_dir() {
+                stack.push(path);
+            } else if path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// Creates a remote directory (and is a no-op if it already exists),
+/// mirroring `mkdir -p` for the single path given.
+fn ensure_remote_dir(sftp: &Sftp, path: &Path) -> Result<(), Box<dyn Error>> {
+    if sftp.stat(path).is_ok() {
+        return Ok(());
+    }
+
+    if let Some(parent) = path.parent() {
+        ensure_remote_dir(sftp, parent)?;
+    }
+
+    match sftp.mkdir(path, 0o755) {
+        Ok(_) => Ok(()),
+        Err(_) if sftp.stat(path).is_ok() => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
@@ -0,0 +1,281 @@
+//! Auth and host-key logic shared by the SCP and SFTP backends, both of
+//! which sit on top of the same `ssh2::Session`.
+
+use crate::TransferConfig;
+use console::style;
+use dialoguer::{Confirm, Password};
+use ssh2::{CheckResult, KnownHostFileKind, Session};
+use std::error::Error;
+use std::net::TcpStream;
+use std::path::Path;
+
+/// A connected-or-not `ssh2::Session`, with the connect sequence (TCP
+/// connect, handshake, host-key verification) and the session accessor
+/// shared by `ScpTransfer` and `SftpTransfer` so neither has to duplicate
+/// them.
+#[derive(Default)]
+pub struct SshConnection {
+    session: Option<Session>,
+}
+
+impl SshConnection {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connect(&mut self, config: &TransferConfig) -> Result<(), Box<dyn Error>> {
+        let tcp = TcpStream::connect(format!("{}:{}", config.remote_host, config.port))?;
+        log::debug!("TCP connected to {}:{}", config.remote_host, config.port);
+
+        let mut sess = Session::new()?;
+        sess.set_tcp_stream(tcp);
+        sess.handshake()?;
+        log::debug!("SSH handshake complete");
+
+        verify_host_key(&sess, &config.remote_host, config.port)?;
+        self.session = Some(sess);
+        Ok(())
+    }
+
+    pub fn authenticate(&mut self, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+        let sess = self.session()?;
+        authenticate(sess, config)
+    }
+
+    pub fn session(&mut self) -> Result<&mut Session, Box<dyn Error>> {
+        self.session.as_mut().ok_or_else(|| "Not connected".into())
+    }
+
+    pub fn disconnect(&mut self) {
+        self.session = None;
+    }
+}
+
+/// Which authentication method(s) to try. `Auto` (the default) tries them
+/// all in the usual order; the others restrict `authenticate` to a single
+/// method, which is what `--auth` on the CLI selects for scripted runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum AuthMethod {
+    #[default]
+    Auto,
+    Agent,
+    Key,
+    Password,
+}
+
+/// Checks the server's host key against `~/.ssh/known_hosts`, prompting to
+/// trust-on-first-use when the host is unknown and aborting loudly on a
+/// mismatch (which indicates a changed key or a possible MITM).
+pub fn verify_host_key(sess: &Session, host: &str, port: u16) -> Result<(), Box<dyn Error>> {
+    let mut known_hosts = sess.known_hosts()?;
+
+    let home = std::env::var("HOME")?;
+    let known_hosts_path = Path::new(&home).join(".ssh").join("known_hosts");
+    let _ = known_hosts.read_file(&known_hosts_path, KnownHostFileKind::OpenSSH);
+
+    let (key, key_type) = sess
+        .host_key()
+        .ok_or("Server did not present a host key")?;
+
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => {
+            println!("{}", style("✅ Host key verified against known_hosts").green());
+            Ok(())
+        }
+        CheckResult::Mismatch => Err(format!(
+            "Host key for {} does not match known_hosts! Possible man-in-the-middle attack.",
+            host
+        )
+        .into()),
+        CheckResult::NotFound => {
+            println!(
+                "{}",
+                style(format!("⚠️  Host {} is not in known_hosts", host)).yellow()
+            );
+
+            let trust = Confirm::new()
+                .with_prompt("Trust this host and continue?")
+                .default(false)
+                .interact()?;
+
+            if !trust {
+                return Err("Host key not trusted".into());
+            }
+
+            known_hosts.add(host, key, "iscp-added", key_type.into())?;
+            known_hosts.write_file(&known_hosts_path, KnownHostFileKind::OpenSSH)?;
+            println!("{}", style("✅ Host key added to known_hosts").green());
+            Ok(())
+        }
+        CheckResult::Failure => Err("Failed to check host key against known_hosts".into()),
+    }
+}
+
+/// Authenticates a session using `config.auth_method`. `Auto` tries
+/// ssh-agent identities, then on-disk key files (or `config.identity_file`
+/// if one was given), then falls back to a password prompt; the other
+/// variants restrict the attempt to a single method, for scripted runs
+/// that already know which one applies.
+pub fn authenticate(sess: &mut Session, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+    match config.auth_method {
+        AuthMethod::Agent => return Ok(try_agent_auth(sess, config)),
+        AuthMethod::Key => return try_key_auth(sess, config),
+        AuthMethod::Password => return try_password_auth(sess, config),
+        AuthMethod::Auto => {}
+    }
+
+    log::debug!("trying ssh-agent auth for {}", config.username);
+    if try_agent_auth(sess, config) {
+        log::info!("authenticated via ssh-agent");
+        println!("{}", style("✅ Authenticated via ssh-agent").green());
+        return Ok(true);
+    }
+
+    if try_key_auth(sess, config)? {
+        return Ok(true);
+    }
+
+    log::debug!("trying password auth for {}", config.username);
+    println!(
+        "{}",
+        style("🔐 SSH key authentication failed, trying password authentication").yellow()
+    );
+    try_password_auth(sess, config)
+}
+
+/// Tries every identity offered by a running ssh-agent. Returns `false`
+/// (without treating it as an error) if no agent is reachable or it has no
+/// identities loaded, so the caller falls through to key-file/password auth.
+fn try_agent_auth(sess: &mut Session, config: &TransferConfig) -> bool {
+    let mut agent = match sess.agent() {
+        Ok(agent) => agent,
+        Err(_) => {
+            log::debug!("no ssh-agent available");
+            return false;
+        }
+    };
+
+    if agent.connect().is_err() {
+        log::debug!("failed to connect to ssh-agent");
+        return false;
+    }
+
+    if agent.list_identities().is_err() {
+        log::debug!("failed to list ssh-agent identities");
+        return false;
+    }
+
+    let identities = match agent.identities() {
+        Ok(identities) => identities,
+        Err(_) => {
+            log::debug!("failed to read ssh-agent identities");
+            return false;
+        }
+    };
+
+    if identities.is_empty() {
+        log::debug!("ssh-agent has no identities loaded");
+        return false;
+    }
+
+    println!(
+        "{}",
+        style(format!("🔑 Trying ssh-agent ({} identities)", identities.len())).blue()
+    );
+
+    for identity in identities {
+        if agent.userauth(&config.username, &identity).is_ok() && sess.authenticated() {
+            return true;
+        }
+    }
+
+    log::debug!("ssh-agent identities all rejected for {}", config.username);
+    false
+}
+
+/// Tries `config.identity_file` if one was given, otherwise the default
+/// `id_rsa`/`id_ed25519`/`id_ecdsa` key files, prompting for a passphrase
+/// whenever a key needs one.
+fn try_key_auth(sess: &mut Session, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+    let home = std::env::var("HOME").ok();
+
+    let key_paths: Vec<String> = if let Some(identity_file) = &config.identity_file {
+        vec![identity_file.clone()]
+    } else if let Some(home) = &home {
+        vec![
+            format!("{}/.ssh/id_rsa", home),
+            format!("{}/.ssh/id_ed25519", home),
+            format!("{}/.ssh/id_ecdsa", home),
+        ]
+    } else {
+        Vec::new()
+    };
+
+    for key_path in &key_paths {
+        if !Path::new(key_path).exists() {
+            continue;
+        }
+
+        log::debug!("trying key file {}", key_path);
+        println!(
+            "{}",
+            style(format!("🔑 Trying SSH key: {}", key_path)).blue()
+        );
+
+        let key_path = Path::new(key_path);
+
+        if sess
+            .userauth_pubkey_file(&config.username, None, key_path, None)
+            .is_ok()
+        {
+            log::info!("authenticated with key file {} (no passphrase)", key_path.display());
+            println!(
+                "{}",
+                style("✅ Authenticated with SSH key (no passphrase)").green()
+            );
+            return Ok(true);
+        }
+
+        println!("{}", style("🔐 SSH key requires passphrase").yellow());
+        let passphrase: String = Password::new()
+            .with_prompt("🔑 SSH key passphrase")
+            .interact()
+            .expect("Failed to read passphrase");
+
+        if sess
+            .userauth_pubkey_file(&config.username, None, key_path, Some(passphrase.as_str()))
+            .is_ok()
+        {
+            log::info!("authenticated with key file {} (with passphrase)", key_path.display());
+            println!(
+                "{}",
+                style("✅ Authenticated with SSH key (with passphrase)").green()
+            );
+            return Ok(true);
+        }
+
+        log::debug!("key file {} rejected", key_path.display());
+    }
+
+    Ok(false)
+}
+
+fn try_password_auth(sess: &mut Session, config: &TransferConfig) -> Result<bool, Box<dyn Error>> {
+    let password: String = Password::new()
+        .with_prompt("🔑 Password")
+        .interact()
+        .expect("Failed to read password");
+
+    match sess.userauth_password(&config.username, &password) {
+        Ok(_) => {
+            log::info!("authenticated with password");
+            println!("{}", style("✅ Authenticated with password").green());
+            Ok(true)
+        }
+        _ => {
+            log::warn!("password auth rejected for {}", config.username);
+            println!("{}", style("❌ Password authentication failed").red());
+            Ok(false)
+        }
+    }
+}